@@ -0,0 +1,98 @@
+use std::env;
+
+/*********/
+/* Shell */
+/*********/
+
+/* Abstraction over the syntax differences between shells so the rest of the
+ * crate can ask for "set this variable" without caring whether the active
+ * shell is POSIX, fish, or PowerShell. */
+pub trait Shell {
+    /* Emit a command that exports NAME=VALUE in the live shell. */
+    fn setenv(&self, name: &str, value: &str) -> String;
+
+    /* Emit a command that removes NAME from the live shell. */
+    fn unsetenv(&self, name: &str) -> String;
+
+    /* Emit a command that defines NAME as an alias for CMD. */
+    fn alias(&self, name: &str, cmd: &str) -> String;
+
+    /* Emit a command that removes the alias NAME. */
+    fn unalias(&self, name: &str) -> String;
+}
+
+struct Posix;
+
+impl Shell for Posix {
+    fn setenv(&self, name: &str, value: &str) -> String {
+        format!("export {}=\"{}\"", name, value)
+    }
+
+    fn unsetenv(&self, name: &str) -> String {
+        format!("unset {}", name)
+    }
+
+    fn alias(&self, name: &str, cmd: &str) -> String {
+        format!("alias {}=\"{}\"", name, cmd)
+    }
+
+    fn unalias(&self, name: &str) -> String {
+        format!("unalias {}", name)
+    }
+}
+
+struct Fish;
+
+impl Shell for Fish {
+    fn setenv(&self, name: &str, value: &str) -> String {
+        format!("set -gx {} \"{}\"", name, value)
+    }
+
+    fn unsetenv(&self, name: &str) -> String {
+        format!("set -e {}", name)
+    }
+
+    fn alias(&self, name: &str, cmd: &str) -> String {
+        format!("alias {} '{}'", name, cmd)
+    }
+
+    fn unalias(&self, name: &str) -> String {
+        format!("functions -e {}", name)
+    }
+}
+
+struct PowerShell;
+
+impl Shell for PowerShell {
+    fn setenv(&self, name: &str, value: &str) -> String {
+        format!("$env:{} = \"{}\"", name, value)
+    }
+
+    fn unsetenv(&self, name: &str) -> String {
+        format!("Remove-Item Env:{}", name)
+    }
+
+    fn alias(&self, name: &str, cmd: &str) -> String {
+        format!("Set-Alias {} \"{}\"", name, cmd)
+    }
+
+    fn unalias(&self, name: &str) -> String {
+        format!("Remove-Item Alias:{}", name)
+    }
+}
+
+/* Detect the user's active shell and return the matching implementation.
+ * Falls back to POSIX syntax, which is understood by sh/bash/zsh/ksh. */
+pub fn get_shell() -> Box<dyn Shell> {
+    match env::var("SHELL") {
+        Ok(shell) if shell.contains("fish") => Box::new(Fish),
+        Ok(_) => Box::new(Posix),
+        Err(_) => {
+            if env::var_os("PSModulePath").is_some() {
+                Box::new(PowerShell)
+            } else {
+                Box::new(Posix)
+            }
+        }
+    }
+}
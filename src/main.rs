@@ -1,14 +1,14 @@
 use clap::clap_app;
 use clap::AppSettings;
-use scan_fmt::scan_fmt;
 
 use dirs;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::env;
 use std::fs;
-use std::fs::{File, OpenOptions};
+use std::fs::File;
 use std::io;
-use std::io::{BufRead, BufReader};
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
 
@@ -20,8 +20,11 @@ const ENV_VAR: &str  = "ALIAS_ENV";
 const NO_ENV_ACTIVE: &str = "NO ENV";
 
 const ALIAS_FILE: &str = "aliases";
+const VARS_FILE: &str = "vars";
+const PARENT_FILE: &str = "parent";
 
 const VALID_ENV_REGEX: &str = "[-_.A-Za-z0-9]+";
+const LEGACY_ALIAS_REGEX: &str = "^alias (\\S+)=\"(.*)\"$";
 
 /********/
 /* Util */
@@ -56,6 +59,112 @@ fn get_alias_file(env: &str) -> PathBuf {
     return root_dir;
 }
 
+fn get_vars_file(env: &str) -> PathBuf {
+    let mut root_dir = get_root_path();
+    root_dir.push(env);
+    root_dir.push(VARS_FILE);
+    return root_dir;
+}
+
+/* Read an env's vars file, treating a missing file as empty. Envs created
+ * before per-env variables existed have no vars file on disk, the same way
+ * an env with no legacy aliases has nothing to migrate. */
+fn read_vars_file(vars_file: &Path) -> String {
+    match fs::read_to_string(vars_file) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => error(&format!("Unable to access variables: {}", e))
+    }
+}
+
+fn get_parent_file(env: &str) -> PathBuf {
+    let mut root_dir = get_root_path();
+    root_dir.push(env);
+    root_dir.push(PARENT_FILE);
+    return root_dir;
+}
+
+/* Read the parent env an env extends, if any. */
+fn get_parent(env: &str) -> Option<String> {
+    let parent_file = get_parent_file(env);
+    if !Path::exists(&parent_file) {
+        return None;
+    }
+
+    let mut f = err_check(
+        File::open(&parent_file),
+        "Unable to access parent"
+    );
+    let mut contents = String::new();
+    err_check(f.read_to_string(&mut contents), "Unable to read parent");
+
+    let trimmed = contents.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+/* Record the parent env an env extends. */
+fn set_parent(env: &str, parent: &str) {
+    let parent_file = get_parent_file(env);
+    let mut f = err_check(
+        File::create(&parent_file),
+        "Cannot create file - insufficient permissions?"
+    );
+    err_check(write!(f, "{}", parent), "Unable to write parent");
+}
+
+/* Resolve the full ancestor chain of an env, base ancestor first and the env
+ * itself last. Errors on a missing parent or an inheritance cycle. */
+fn resolve_chain(env: &str) -> Vec<String> {
+    let mut chain = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = env.to_string();
+
+    loop {
+        if !visited.insert(current.clone()) {
+            error(&format!("Cycle detected in environment inheritance at '{}'.", current));
+        }
+        chain.push(current.clone());
+
+        match get_parent(&current) {
+            Some(parent) => {
+                if !env_exists(&parent) {
+                    error(&format!("Parent environment {} does not exist.", parent));
+                }
+                current = parent;
+            },
+            None => break
+        }
+    }
+
+    chain.reverse();
+    return chain;
+}
+
+/* Names of the envs that declare `env` as their direct parent. */
+fn get_children(env: &str) -> Vec<String> {
+    let root_dir = get_root_path();
+    let envs = fs::read_dir(root_dir).expect("Unable to read directory");
+    let mut children = Vec::new();
+
+    for dir in envs {
+        let name = dir.expect("Unable to read environment")
+            .file_name()
+            .into_string()
+            .unwrap();
+        if let Some(parent) = get_parent(&name) {
+            if parent == env {
+                children.push(name);
+            }
+        }
+    }
+
+    return children;
+}
+
 fn err_check<T, K>(r: Result<T, K>, err: &str) -> T {
     match r {
         Err(_) => {
@@ -101,24 +210,87 @@ fn env_exists(env: &str) -> bool {
     return false;
 }
 
-/* Delete alias from file. Return true if successful, false if doesn't exist. */
-fn delete_alias_from_file(file: &str, alias: &str) -> bool {
-    let search_text = format!("alias {}=", alias);
+/* A single alias's stored command and optional human-readable description. */
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct AliasEntry {
+    command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+/* Alias name -> entry, persisted as TOML in each env's alias file. */
+type AliasMap = BTreeMap<String, AliasEntry>;
 
-    let mut f = err_check(
-        File::open(file),
+/* Parse an alias file that predates the TOML format: plain
+ * `alias name="command"` lines, one per alias. Uses a regex rather than
+ * scan_fmt since the command itself may contain `=` or spaces and scan_fmt
+ * would capture only the quote character and truncate at the first space. */
+fn migrate_legacy_aliases(contents: &str) -> AliasMap {
+    let mut map = AliasMap::new();
+    let r = Regex::new(LEGACY_ALIAS_REGEX).unwrap();
+
+    for line in contents.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match r.captures(line) {
+            Some(caps) => {
+                let alias = caps[1].to_string();
+                let command = caps[2].to_string();
+                map.insert(alias, AliasEntry { command, description: None });
+            },
+            None => error("Invalid alias file")
+        }
+    }
+
+    return map;
+}
+
+/* Read an env's alias map, migrating a legacy plain-text alias file to TOML
+ * in place on first touch. */
+fn read_alias_map(env: &str) -> AliasMap {
+    let alias_file = get_alias_file(env);
+    let contents = err_check(
+        fs::read_to_string(&alias_file),
         "Unable to access aliases"
     );
-    let reader = BufReader::new(&f);
 
-    /* Filter out the line containing the alias to delete. */
-    let lines : Vec<String> = reader.lines()
-        .map(|x| x.unwrap())
+    match toml::from_str::<AliasMap>(&contents) {
+        Ok(map) => map,
+        Err(_) => {
+            let map = migrate_legacy_aliases(&contents);
+            write_alias_map(env, &map);
+            map
+        }
+    }
+}
+
+/* Write an env's alias map back out as TOML. */
+fn write_alias_map(env: &str, map: &AliasMap) {
+    let alias_file = get_alias_file(env);
+    let serialized = err_check(toml::to_string(map), "Unable to serialize aliases");
+
+    let mut f = err_check(
+        File::create(&alias_file),
+        "Unable to write to file"
+    );
+    err_check(f.write_all(serialized.as_bytes()), "Unable to write to file");
+}
+
+/* Delete a variable from file. Return true if successful, false if doesn't exist. */
+fn delete_var_from_file(file: &str, name: &str) -> bool {
+    let search_text = format!("{}=", name);
+
+    /* A missing vars file (legacy env) has no variables to delete. */
+    let contents = read_vars_file(Path::new(file));
+    let lines : Vec<String> = contents.lines()
+        .map(String::from)
         .collect();
     let new_lines : Vec<String> = lines.clone()
         .into_iter()
         .filter_map(
-            |line| if !line.contains(&search_text) { Some(line) } else { None },
+            |line| if !line.starts_with(&search_text) { Some(line) } else { None },
         ).collect();
 
     /* If no line was deleted, it doesn't exist. */
@@ -126,7 +298,7 @@ fn delete_alias_from_file(file: &str, alias: &str) -> bool {
         return false;
     }
 
-    f = err_check(
+    let mut f = err_check(
         File::create(file),
         "Unable to write to file"
     );
@@ -147,29 +319,25 @@ fn set_alias_var(output: &mut String, status: &str) {
     add_output_line(output, &cmd);
 }
 
-/* Unalias all commands in an env. */
-fn unalias_all(output: &mut String, env: &str) {
-    /* Open alias file. */
-    let alias_file = get_alias_file(&env);
-    let f = err_check(
-        File::open(alias_file),
-        "Unable to access aliases"
-    );
-    let reader = BufReader::new(&f);
-
-    /* Construct the string to unset the aliases. */
-    let unset_aliases : String = reader.lines()
-        .map(|x| {
-            if let Ok((alias, _)) = scan_fmt!(&x.unwrap(), "alias {}={}", String, String) {
-                format!("unalias {}", alias)
-            } else {
-                error("Invalid alias file");
-            }
-        })
+/* Names of the aliases defined in an env. */
+fn get_alias_names(env: &str) -> Vec<String> {
+    read_alias_map(env).into_keys().collect()
+}
+
+/* Unalias the union of aliases across an env's entire ancestor chain. */
+fn unalias_chain(output: &mut String, env: &str) {
+    let shell = lib::get_shell();
+    let mut names : HashSet<String> = HashSet::new();
+    for ancestor in resolve_chain(env) {
+        names.extend(get_alias_names(&ancestor));
+    }
+
+    let unset_aliases : String = names
+        .into_iter()
+        .map(|alias| shell.unalias(&alias))
         .collect::<Vec<String>>()
         .join(";");
 
-    /* Add unset aliases to output string. */
     if unset_aliases != "" {
         add_output_line(output, &unset_aliases);
     }
@@ -177,16 +345,12 @@ fn unalias_all(output: &mut String, env: &str) {
 
 /* Alias all commands in an env. */
 fn alias_all(output: &mut String, env: &str) {
-    let alias_file = get_alias_file(&env);
-    let new_f = err_check(
-        File::open(alias_file),
-        "Unable to access aliases"
-    );
-    let reader = BufReader::new(&new_f);
+    let shell = lib::get_shell();
 
     /* Construct the string to set the new aliases. */
-    let set_aliases : String = reader.lines()
-        .map(|x| x.unwrap())
+    let set_aliases : String = read_alias_map(env)
+        .into_iter()
+        .map(|(alias, entry)| shell.alias(&alias, &entry.command))
         .collect::<Vec<String>>()
         .join(";");
 
@@ -196,6 +360,79 @@ fn alias_all(output: &mut String, env: &str) {
     }
 }
 
+/* Alias all commands across an env's entire ancestor chain, base ancestor
+ * first so that a child's aliases override its parent's. */
+fn alias_chain(output: &mut String, env: &str) {
+    for ancestor in resolve_chain(env) {
+        alias_all(output, &ancestor);
+    }
+}
+
+/* Parse a "NAME=VALUE" line, keeping the entire remainder after the first
+ * `=` as the value so values containing whitespace or `=` survive intact. */
+fn parse_var_line(line: &str) -> (String, String) {
+    match line.splitn(2, '=').collect::<Vec<&str>>().as_slice() {
+        [name, value] => (name.to_string(), value.to_string()),
+        _ => error("Invalid variable file")
+    }
+}
+
+/* Parse the names of the variables exported by an env. */
+fn get_var_names(env: &str) -> Vec<String> {
+    let contents = read_vars_file(&get_vars_file(env));
+
+    contents.lines()
+        .map(|line| parse_var_line(line).0)
+        .collect()
+}
+
+/* Unset the union of variables across an env's entire ancestor chain. */
+fn unsetvar_chain(output: &mut String, env: &str) {
+    let shell = lib::get_shell();
+    let mut names : HashSet<String> = HashSet::new();
+    for ancestor in resolve_chain(env) {
+        names.extend(get_var_names(&ancestor));
+    }
+
+    let unset_vars : String = names
+        .into_iter()
+        .map(|name| shell.unsetenv(&name))
+        .collect::<Vec<String>>()
+        .join(";");
+
+    if unset_vars != "" {
+        add_output_line(output, &unset_vars);
+    }
+}
+
+/* Export all variables introduced by an env. */
+fn setvar_all(output: &mut String, env: &str) {
+    let contents = read_vars_file(&get_vars_file(env));
+    let shell = lib::get_shell();
+
+    /* Construct the string to export the variables. */
+    let set_vars : String = contents.lines()
+        .map(|line| {
+            let (name, value) = parse_var_line(line);
+            shell.setenv(&name, &value)
+        })
+        .collect::<Vec<String>>()
+        .join(";");
+
+    /* Add set variables to output string. */
+    if set_vars != "" {
+        add_output_line(output, &set_vars);
+    }
+}
+
+/* Export all variables across an env's entire ancestor chain, base ancestor
+ * first so that a child's vars override its parent's. */
+fn setvar_chain(output: &mut String, env: &str) {
+    for ancestor in resolve_chain(env) {
+        setvar_all(output, &ancestor);
+    }
+}
+
 /*********/
 /* Setup */
 /*********/
@@ -222,12 +459,96 @@ fn setup(output: &mut String) {
     }
 }
 
+/***************/
+/* Completions */
+/***************/
+
+/* Print the name of every environment, one per line, for shell completion. */
+fn list_envs() {
+    let root_dir = get_root_path();
+    let envs = fs::read_dir(root_dir).expect("Unable to read directory");
+
+    for dir in envs {
+        let env = dir.expect("Unable to read environment")
+            .file_name()
+            .into_string()
+            .unwrap();
+        println!("{}", env);
+    }
+}
+
+/* Print the aliases of the active environment, one per line, for shell
+ * completion. Prints nothing if no environment is active. */
+fn list_aliases() {
+    let env = match env::var_os(ENV_VAR) {
+        Some(cur_env) if cur_env != NO_ENV_ACTIVE => cur_env,
+        _ => return
+    }.into_string().unwrap();
+
+    for alias in get_alias_names(&env) {
+        println!("{}", alias);
+    }
+}
+
+/* Print a completion script for the given shell. */
+fn completions(shell: &str) {
+    match shell {
+        "bash" => print!("{}", r#"_alienv_complete() {
+    local cur prev
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+
+    case "${prev}" in
+        load|delete|setparent|clone|rename)
+            COMPREPLY=( $(compgen -W "$(alienv list_envs)" -- "${cur}") )
+            ;;
+        rem)
+            COMPREPLY=( $(compgen -W "$(alienv list_aliases)" -- "${cur}") )
+            ;;
+        alienv)
+            COMPREPLY=( $(compgen -W "new delete load show add rem setvar unsetvar setparent clone rename completions" -- "${cur}") )
+            ;;
+    esac
+}
+complete -F _alienv_complete alienv
+"#),
+        "zsh" => print!("{}", r#"#compdef alienv
+_alienv() {
+    local -a subcmds
+    subcmds=(new delete load show add rem setvar unsetvar setparent clone rename completions)
+
+    case ${words[2]} in
+        load|delete|setparent|clone|rename)
+            local -a envs
+            envs=(${(f)"$(alienv list_envs)"})
+            _describe 'environment' envs
+            ;;
+        rem)
+            local -a aliases
+            aliases=(${(f)"$(alienv list_aliases)"})
+            _describe 'alias' aliases
+            ;;
+        *)
+            _describe 'command' subcmds
+            ;;
+    esac
+}
+compdef _alienv alienv
+"#),
+        "fish" => print!("{}", r#"complete -c alienv -f -n "__fish_use_subcommand" -a "new delete load show add rem setvar unsetvar setparent clone rename completions"
+complete -c alienv -f -n "__fish_seen_subcommand_from load delete setparent clone rename" -a "(alienv list_envs)"
+complete -c alienv -f -n "__fish_seen_subcommand_from rem" -a "(alienv list_aliases)"
+"#),
+        _ => error(&format!("Unsupported shell for completions: {}", shell))
+    }
+}
+
 /***************/
 /* Subcommands */
 /***************/
 
 /* Create a new alias environment and switch to it. */
-fn new(output: &mut String, env: &str) {
+fn new(output: &mut String, env: &str, from: Option<&str>) {
     /* Check if env is valid name. */
     if !is_valid_env_name(env) {
         error(&format!("Not a valid environment name. Only numbers, letters, period, underscore, and hyphen allowed."));
@@ -238,6 +559,13 @@ fn new(output: &mut String, env: &str) {
         error(&format!("Environment {} already exists.", env));
     }
 
+    /* Ensure the parent to inherit from, if any, exists. */
+    if let Some(parent) = from {
+        if !env_exists(parent) {
+            error(&format!("Parent environment {} does not exist.", parent));
+        }
+    }
+
     let mut root_dir = get_root_path();
     root_dir.push(env);
 
@@ -248,16 +576,46 @@ fn new(output: &mut String, env: &str) {
     );
 
     /* Create new files. */
-    let mut aliases = root_dir.clone();
-    aliases.push(ALIAS_FILE);
+    write_alias_map(env, &AliasMap::new());
+
+    let mut vars = root_dir.clone();
+    vars.push(VARS_FILE);
     err_check(
-        File::create(aliases),
+        File::create(vars),
         "Cannot create file - insufficient permissions?"
     );
 
+    if let Some(parent) = from {
+        set_parent(env, parent);
+    }
+
     load(output, env);
 }
 
+/* Set (or change) the parent env that an env inherits from. */
+fn setparent(_output: &mut String, env: &str, parent: &str) {
+    if !env_exists(env) {
+        error(&format!("Environment {} does not exist.", env));
+    }
+
+    if !env_exists(parent) {
+        error(&format!("Parent environment {} does not exist.", parent));
+    }
+
+    if env == parent {
+        error("An environment cannot be its own parent.");
+    }
+
+    /* Make sure the new parent doesn't introduce a cycle before persisting
+     * it: if env is already an ancestor of parent, parenting env to parent
+     * would close a loop. */
+    if resolve_chain(parent).contains(&env.to_string()) {
+        error(&format!("Setting {} as the parent of {} would introduce a cycle.", parent, env));
+    }
+
+    set_parent(env, parent);
+}
+
 /* Delete an alias environment. */
 fn delete(output: &mut String, env: &str) {
     let mut root_dir = get_root_path();
@@ -267,10 +625,17 @@ fn delete(output: &mut String, env: &str) {
         error(&format!("No such environment: {}", env));
     }
 
+    /* Refuse to orphan any env that inherits from this one. */
+    let children = get_children(env);
+    if !children.is_empty() {
+        error(&format!("Cannot delete {}: {} depend(s) on it as a parent.", env, children.join(", ")));
+    }
+
     /* Check environment variable. */
     if is_cur_env(env) {
-        /* If deleting current env, unset all aliases and active env. */
-        unalias_all(output, env);
+        /* If deleting current env, unset all aliases/vars and active env. */
+        unalias_chain(output, env);
+        unsetvar_chain(output, env);
 
         /* Unset active env. */
         set_alias_var(output, NO_ENV_ACTIVE);
@@ -280,6 +645,85 @@ fn delete(output: &mut String, env: &str) {
     fs::remove_dir_all(&root_dir).expect("Unable to delete environment");
 }
 
+/* Deep-copy an environment's files under a new name. */
+fn clone_env(_output: &mut String, src: &str, dst: &str) {
+    /* Check if dst is valid name. */
+    if !is_valid_env_name(dst) {
+        error(&format!("Not a valid environment name. Only numbers, letters, period, underscore, and hyphen allowed."));
+    }
+
+    if !env_exists(src) {
+        error(&format!("Environment {} does not exist.", src));
+    }
+
+    if env_exists(dst) {
+        error(&format!("Environment {} already exists.", dst));
+    }
+
+    let mut src_dir = get_root_path();
+    src_dir.push(src);
+
+    let mut dst_dir = get_root_path();
+    dst_dir.push(dst);
+
+    err_check(
+        fs::create_dir(&dst_dir),
+        "Cannot create directory - insufficient permissions?"
+    );
+
+    /* Copy over the aliases, vars, and (if any) parent files. */
+    for file in &[ALIAS_FILE, VARS_FILE, PARENT_FILE] {
+        let mut from = src_dir.clone();
+        from.push(file);
+
+        if Path::exists(&from) {
+            let mut to = dst_dir.clone();
+            to.push(file);
+            err_check(fs::copy(&from, &to), "Unable to copy environment files");
+        }
+    }
+}
+
+/* Rename an environment. */
+fn rename(output: &mut String, old: &str, new: &str) {
+    /* Check if new is valid name. */
+    if !is_valid_env_name(new) {
+        error(&format!("Not a valid environment name. Only numbers, letters, period, underscore, and hyphen allowed."));
+    }
+
+    if !env_exists(old) {
+        error(&format!("Environment {} does not exist.", old));
+    }
+
+    if env_exists(new) {
+        error(&format!("Environment {} already exists.", new));
+    }
+
+    /* Find envs that inherit from the old name before it disappears. */
+    let children = get_children(old);
+
+    let mut old_dir = get_root_path();
+    old_dir.push(old);
+
+    let mut new_dir = get_root_path();
+    new_dir.push(new);
+
+    err_check(
+        fs::rename(&old_dir, &new_dir),
+        "Unable to rename environment"
+    );
+
+    /* Repoint any child envs at the new name. */
+    for child in &children {
+        set_parent(child, new);
+    }
+
+    /* Keep $ALIAS_ENV in sync if the renamed env is the active one. */
+    if is_cur_env(old) {
+        set_alias_var(output, new);
+    }
+}
+
 /* Load a new environment. */
 fn load(output: &mut String, env: &str) {
     /* Ensure env exists. */
@@ -294,16 +738,19 @@ fn load(output: &mut String, env: &str) {
         }
 
         if cur_env != NO_ENV_ACTIVE {
-            /* Unset all current aliases. */
-            unalias_all(output, &cur_env.into_string().unwrap());
+            /* Unset all current aliases and vars. */
+            let cur_env_str = cur_env.into_string().unwrap();
+            unalias_chain(output, &cur_env_str);
+            unsetvar_chain(output, &cur_env_str);
         }
     }
 
     /* Set active env to new env. */
     set_alias_var(output, &env);
 
-    /* Load aliases of new env. */
-    alias_all(output, &env);
+    /* Load aliases and vars of new env, resolving its ancestor chain. */
+    alias_chain(output, &env);
+    setvar_chain(output, &env);
 }
 
 fn show_all(output: &mut String) {
@@ -324,7 +771,7 @@ fn show_all(output: &mut String) {
 }
 
 /* Add a new alias to the current env. */
-fn add_alias(output: &mut String, alias: &str, command: &str) {
+fn add_alias(output: &mut String, alias: &str, command: &str, desc: Option<&str>) {
     let env = match env::var_os(ENV_VAR) {
         Some(cur_env) => {
             if cur_env == NO_ENV_ACTIVE {
@@ -338,22 +785,16 @@ fn add_alias(output: &mut String, alias: &str, command: &str) {
         }
     }.into_string().unwrap();
 
-    /* Add new alias to file. */
-    let alias_file = get_alias_file(&env);
-    let new_alias = format!("alias {}=\"{}\"", alias, command);
-    
-    let mut f = OpenOptions::new()
-        .write(true)
-        .append(true)
-        .open(&alias_file)
-        .unwrap();
-
-    err_check(
-        writeln!(f, "{}", &new_alias),
-        "Unable to write alias."
-    );
+    /* Add new alias to the env's alias map. */
+    let mut map = read_alias_map(&env);
+    map.insert(alias.to_string(), AliasEntry {
+        command: command.to_string(),
+        description: desc.map(|d| d.to_string()),
+    });
+    write_alias_map(&env, &map);
 
     /* Set new alias. */
+    let new_alias = lib::get_shell().alias(alias, command);
     add_output_line(output, &new_alias);
 }
 
@@ -372,17 +813,77 @@ fn remove_alias(output: &mut String, alias: &str) {
         }
     }.into_string().unwrap();
 
-    let alias_file = get_alias_file(&env)
+    /* Remove alias from the env's alias map if it exists. */
+    let mut map = read_alias_map(&env);
+    if map.remove(alias).is_some() {
+        write_alias_map(&env, &map);
+        add_output_line(output, &lib::get_shell().unalias(alias));
+    } else {
+        error("No such alias.");
+    }
+}
+
+/* Add a new exported variable to the current env. */
+fn setvar(output: &mut String, name: &str, value: &str) {
+    let env = match env::var_os(ENV_VAR) {
+        Some(cur_env) => {
+            if cur_env == NO_ENV_ACTIVE {
+                error("No alias env active.");
+            };
+            cur_env
+        },
+        None => {
+            error(&format!("${} does not exist. Rerun setup.", ENV_VAR))
+        }
+    }.into_string().unwrap();
+
+    /* Drop any existing definition of this variable, then append the new one. */
+    let vars_file = get_vars_file(&env);
+    let existing = read_vars_file(&vars_file);
+    let mut lines : Vec<String> = existing.lines()
+        .map(String::from)
+        .filter(|line| parse_var_line(line).0 != name)
+        .collect();
+    lines.push(format!("{}={}", name, value));
+
+    let mut f = err_check(
+        File::create(&vars_file),
+        "Unable to write to file"
+    );
+    for line in &lines {
+        err_check(writeln!(f, "{}", line), "Unable to write variable.");
+    }
+
+    /* Export new variable. */
+    let shell = lib::get_shell();
+    add_output_line(output, &shell.setenv(name, value));
+}
+
+/* Remove an exported variable from the current env. */
+fn unsetvar(output: &mut String, name: &str) {
+    let env = match env::var_os(ENV_VAR) {
+        Some(cur_env) => {
+            if cur_env == NO_ENV_ACTIVE {
+                error("No alias env active.");
+            };
+            cur_env
+        },
+        None => {
+            error(&format!("${} does not exist. Rerun setup.", ENV_VAR))
+        }
+    }.into_string().unwrap();
+
+    let vars_file = get_vars_file(&env)
         .into_os_string()
         .into_string()
         .unwrap();
 
-    /* Delete alias from file if possible. */
-    if delete_alias_from_file(&alias_file, alias) {
-        let temp = String::from(format!("unalias {}", alias));
-        add_output_line(output, &temp);
+    /* Delete variable from file if possible. */
+    if delete_var_from_file(&vars_file, name) {
+        let shell = lib::get_shell();
+        add_output_line(output, &shell.unsetenv(name));
     } else {
-        error("No such alias.");
+        error("No such variable.");
     }
 }
 
@@ -401,6 +902,7 @@ fn main() {
         (@subcommand new =>
             (about: "Creates new environment and switch to it")
             (@arg env_name: +required "Name of the environment to create")
+            (@arg from: --from +takes_value "Parent environment to inherit aliases/vars from")
         )
         (@subcommand delete =>
             (about: "Deletes existing environment")
@@ -417,11 +919,48 @@ fn main() {
             (about: "Adds alias to current environment")
             (@arg alias_name: +required "Name of the alias to add")
             (@arg command:    +required "Command to alias")
+            (@arg desc: --desc +takes_value "Description of the alias")
         )
         (@subcommand rem =>
             (about: "Removes alias from current environment")
             (@arg alias_name: +required "Name of the alias to remove")
         )
+        (@subcommand setvar =>
+            (about: "Sets an exported variable in current environment")
+            (@arg var_name:  +required "Name of the variable to set")
+            (@arg value:     +required "Value of the variable")
+        )
+        (@subcommand unsetvar =>
+            (about: "Unsets an exported variable from current environment")
+            (@arg var_name: +required "Name of the variable to unset")
+        )
+        (@subcommand setparent =>
+            (about: "Sets the parent environment that an environment inherits from")
+            (@arg env_name:    +required "Name of the environment to modify")
+            (@arg parent_name: +required "Name of the parent environment to inherit from")
+        )
+        (@subcommand completions =>
+            (about: "Generates a shell completion script")
+            (@arg shell: +required "Shell to generate completions for (bash, zsh, fish)")
+        )
+        (@subcommand list_envs =>
+            (@setting Hidden)
+            (about: "Lists environment names (used by shell completion)")
+        )
+        (@subcommand list_aliases =>
+            (@setting Hidden)
+            (about: "Lists aliases in the active environment (used by shell completion)")
+        )
+        (@subcommand clone =>
+            (about: "Clones an environment under a new name")
+            (@arg src_name: +required "Name of the environment to clone")
+            (@arg dst_name: +required "Name of the new environment")
+        )
+        (@subcommand rename =>
+            (about: "Renames an environment")
+            (@arg old_name: +required "Current name of the environment")
+            (@arg new_name: +required "New name for the environment")
+        )
     )
     .setting(AppSettings::DisableVersion)
     .setting(AppSettings::VersionlessSubcommands)
@@ -429,9 +968,21 @@ fn main() {
     .map_err(|e| err_clap(e))
     .expect("Invalid arguments");
     
-    if let Some(matches) = matches.subcommand_matches("new") {
-        new(&mut output, matches.value_of("env_name").unwrap());
-        
+    if let Some(_) = matches.subcommand_matches("list_envs") {
+        list_envs();
+        return;
+
+    } else if let Some(_) = matches.subcommand_matches("list_aliases") {
+        list_aliases();
+        return;
+
+    } else if let Some(matches) = matches.subcommand_matches("completions") {
+        completions(matches.value_of("shell").unwrap());
+        return;
+
+    } else if let Some(matches) = matches.subcommand_matches("new") {
+        new(&mut output, matches.value_of("env_name").unwrap(), matches.value_of("from"));
+
     } else if let Some(matches) = matches.subcommand_matches("delete") {
         delete(&mut output, matches.value_of("env_name").unwrap());
         
@@ -444,10 +995,30 @@ fn main() {
     } else if let Some(matches) = matches.subcommand_matches("add") {
         let alias = matches.value_of("alias_name").unwrap();
         let command = matches.value_of("command").unwrap();
-        add_alias(&mut output, alias, command);
+        let desc = matches.value_of("desc");
+        add_alias(&mut output, alias, command, desc);
     } else if let Some(matches) = matches.subcommand_matches("rem") {
         let alias = matches.value_of("alias_name").unwrap();
         remove_alias(&mut output, alias);
+    } else if let Some(matches) = matches.subcommand_matches("setvar") {
+        let name = matches.value_of("var_name").unwrap();
+        let value = matches.value_of("value").unwrap();
+        setvar(&mut output, name, value);
+    } else if let Some(matches) = matches.subcommand_matches("unsetvar") {
+        let name = matches.value_of("var_name").unwrap();
+        unsetvar(&mut output, name);
+    } else if let Some(matches) = matches.subcommand_matches("setparent") {
+        let env_name = matches.value_of("env_name").unwrap();
+        let parent_name = matches.value_of("parent_name").unwrap();
+        setparent(&mut output, env_name, parent_name);
+    } else if let Some(matches) = matches.subcommand_matches("clone") {
+        let src = matches.value_of("src_name").unwrap();
+        let dst = matches.value_of("dst_name").unwrap();
+        clone_env(&mut output, src, dst);
+    } else if let Some(matches) = matches.subcommand_matches("rename") {
+        let old_name = matches.value_of("old_name").unwrap();
+        let new_name = matches.value_of("new_name").unwrap();
+        rename(&mut output, old_name, new_name);
     }
     
     println!("{}", output);